@@ -0,0 +1,19 @@
+//! Support for encoding, decoding, and manipulating Minecraft's NBT (Named Binary Tag) format.
+
+pub mod read;
+pub mod repr;
+pub mod snbt;
+pub mod stream;
+pub mod tag;
+#[cfg(test)]
+mod test_util;
+pub mod write;
+pub mod writer;
+
+pub use read::*;
+pub use repr::*;
+pub use snbt::{parse_snbt, SnbtError};
+pub use stream::{NbtEvent, NbtStreamParser};
+pub use tag::{NbtCompound, NbtList, NbtTag};
+pub use write::{write_nbt_gz_compressed, write_nbt_uncompressed, write_nbt_zlib_compressed};
+pub use writer::NbtWriter;
@@ -1,10 +1,94 @@
 use crate::*;
 use byteorder::{BigEndian, ReadBytesExt};
 use flate2::read::{GzDecoder, ZlibDecoder};
-use std::io::{Error, ErrorKind, Read, Result};
+use std::io::{self, Error, ErrorKind, Read, Result};
+
+/// Options controlling how binary NBT data is read, primarily to guard against hostile input
+/// such as deeply-nested or oversized payloads.
+#[derive(Clone, Copy, Debug)]
+pub struct ReadOptions {
+    /// The maximum nesting depth of compounds and lists allowed while reading. Exceeding this
+    /// depth causes the read to fail rather than overflow the stack. Defaults to `512`.
+    pub max_depth: usize,
+    /// When `true`, the contents of `TAG_Byte_Array`/`TAG_Int_Array`/`TAG_Long_Array` payloads
+    /// are skipped over rather than read into a buffer, leaving an empty array in their place.
+    /// Useful for scanning the shape of large files with bounded memory. Defaults to `false`.
+    pub skip_arrays: bool,
+    /// The maximum number of elements allowed in a single list or array. List/array lengths are
+    /// read directly from the input as an `i32`, so without this bound a crafted file can claim
+    /// billions of elements (or, via a negative length, `usize::MAX` after the cast) and force a
+    /// multi-gigabyte allocation, or an immediate capacity-overflow panic, before a single
+    /// element is read. Defaults to `16 * 1024 * 1024` (16 Mi elements).
+    pub max_len: usize,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            max_depth: 512,
+            skip_arrays: false,
+            max_len: 16 * 1024 * 1024,
+        }
+    }
+}
+
+// Caps how many elements we'll reserve space for up front. Lengths are validated against
+// `ReadOptions::max_len` before we get here, but we still grow in bounded chunks rather than
+// reserving the full (attacker-controlled) length in one shot, so a large-but-permitted length
+// backed by a short stream fails fast instead of allocating memory for data that never arrives.
+//
+// Shared with `stream`, whose pull-based parser is just as exposed to hostile array/list lengths
+// as this tree reader is.
+pub(crate) const PREALLOCATE_CAP: usize = 8192;
+
+// Rejects array/list lengths that exceed the configured per-collection maximum before any
+// allocation happens.
+//
+// Shared with `stream`, whose pull-based parser is just as exposed to hostile array/list lengths
+// as this tree reader is.
+pub(crate) fn check_len(len: usize, opts: &ReadOptions) -> Result<()> {
+    if len > opts.max_len {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "array or list length exceeds the configured maximum",
+        ));
+    }
+    Ok(())
+}
+
+// Advances `source` past an array of `len` elements of `elem_size` bytes each, without allocating
+// a buffer for it, used when `ReadOptions::skip_arrays` is set.
+//
+// Shared with `stream`, whose pull-based parser is just as exposed to hostile array/list lengths
+// as this tree reader is.
+pub(crate) fn skip_array<R: Read>(source: &mut R, len: usize, elem_size: u64) -> Result<()> {
+    let byte_len = (len as u64).checked_mul(elem_size).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "array length overflows a u64 byte count",
+        )
+    })?;
+
+    let copied = io::copy(&mut source.take(byte_len), &mut io::sink())?;
+    if copied != byte_len {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
+    }
+    Ok(())
+}
 
 /// Reads uncompressed binary NBT data from the given source.
 pub fn read_nbt_uncompressed<R: Read>(source: &mut R) -> Result<(NbtCompound, String)> {
+    read_nbt_uncompressed_with_opts(source, ReadOptions::default())
+}
+
+/// Reads uncompressed binary NBT data from the given source, observing the given [`ReadOptions`].
+pub fn read_nbt_uncompressed_with_opts<R: Read>(
+    source: &mut R,
+    opts: ReadOptions,
+) -> Result<(NbtCompound, String)> {
     let root_id = source.read_u8()?;
     if root_id != 0xA {
         return Err(Error::new(
@@ -14,7 +98,7 @@ pub fn read_nbt_uncompressed<R: Read>(source: &mut R) -> Result<(NbtCompound, St
     }
 
     let root_name = read_string(source)?;
-    match read_tag_body(source, 0xA) {
+    match read_tag_body(source, 0xA, &opts, 0) {
         Ok(NbtTag::Compound(compound)) => Ok((compound, root_name)),
         Err(e) => Err(e),
         _ => unreachable!(),
@@ -27,13 +111,31 @@ pub fn read_nbt_zlib_compressed<R: Read>(source: &mut R) -> Result<(NbtCompound,
     read_nbt_uncompressed(&mut ZlibDecoder::new(source))
 }
 
+/// Wraps the given source in a zlib decoder, then passes the wrapped source and the given
+/// [`ReadOptions`] to the uncompressed reader function.
+pub fn read_nbt_zlib_compressed_with_opts<R: Read>(
+    source: &mut R,
+    opts: ReadOptions,
+) -> Result<(NbtCompound, String)> {
+    read_nbt_uncompressed_with_opts(&mut ZlibDecoder::new(source), opts)
+}
+
 /// Wraps the given source in a gz decoder, then passes the wrapped source to the uncompressed
 /// reader function.
 pub fn read_nbt_gz_compressed<R: Read>(source: &mut R) -> Result<(NbtCompound, String)> {
     read_nbt_uncompressed(&mut GzDecoder::new(source))
 }
 
-fn read_tag_body<R: Read>(source: &mut R, id: u8) -> Result<NbtTag> {
+/// Wraps the given source in a gz decoder, then passes the wrapped source and the given
+/// [`ReadOptions`] to the uncompressed reader function.
+pub fn read_nbt_gz_compressed_with_opts<R: Read>(
+    source: &mut R,
+    opts: ReadOptions,
+) -> Result<(NbtCompound, String)> {
+    read_nbt_uncompressed_with_opts(&mut GzDecoder::new(source), opts)
+}
+
+fn read_tag_body<R: Read>(source: &mut R, id: u8, opts: &ReadOptions, depth: usize) -> Result<NbtTag> {
     let tag = match id {
         0x1 => NbtTag::Byte(source.read_i8()?),
         0x2 => NbtTag::Short(source.read_i16::<BigEndian>()?),
@@ -43,18 +145,28 @@ fn read_tag_body<R: Read>(source: &mut R, id: u8) -> Result<NbtTag> {
         0x6 => NbtTag::Double(source.read_f64::<BigEndian>()?),
         0x7 => {
             let len = source.read_i32::<BigEndian>()? as usize;
-            let mut array = vec![0_i8; len];
+            check_len(len, opts)?;
 
-            for i in 0 .. len {
-                array[i] = source.read_i8()?;
-            }
+            if opts.skip_arrays {
+                skip_array(source, len, 1)?;
+                NbtTag::ByteArray(Vec::new())
+            } else {
+                let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
+
+                for _ in 0 .. len {
+                    array.push(source.read_i8()?);
+                }
 
-            NbtTag::ByteArray(array)
+                NbtTag::ByteArray(array)
+            }
         }
         0x8 => NbtTag::String(read_string(source)?),
         0x9 => {
+            let depth = check_depth(depth, opts)?;
+
             let type_id = source.read_u8()?;
             let len = source.read_i32::<BigEndian>()? as usize;
+            check_len(len, opts)?;
 
             // Make sure we don't have a list of TAG_End unless it's empty or an invalid type
             if type_id > 0xC || (type_id == 0 && len > 0) {
@@ -68,21 +180,23 @@ fn read_tag_body<R: Read>(source: &mut R, id: u8) -> Result<NbtTag> {
                 return Ok(NbtTag::List(NbtList::new()));
             }
 
-            let mut list = NbtList::with_capacity(len);
+            let mut list = NbtList::with_capacity(len.min(PREALLOCATE_CAP));
             for _ in 0 .. len {
-                list.push(read_tag_body(source, type_id)?);
+                list.push(read_tag_body(source, type_id, opts, depth)?);
             }
 
             NbtTag::List(list)
         }
         0xA => {
+            let depth = check_depth(depth, opts)?;
+
             let mut compound = NbtCompound::new();
             let mut tag_id = source.read_u8()?;
 
             // Read until TAG_End
             while tag_id != 0x0 {
                 let name = read_string(source)?;
-                let tag = read_tag_body(source, tag_id)?;
+                let tag = read_tag_body(source, tag_id, opts, depth)?;
                 compound.insert(name, tag);
                 tag_id = source.read_u8()?;
             }
@@ -91,23 +205,37 @@ fn read_tag_body<R: Read>(source: &mut R, id: u8) -> Result<NbtTag> {
         }
         0xB => {
             let len = source.read_i32::<BigEndian>()? as usize;
-            let mut array = vec![0_i32; len];
+            check_len(len, opts)?;
 
-            for i in 0 .. len {
-                array[i] = source.read_i32::<BigEndian>()?;
-            }
+            if opts.skip_arrays {
+                skip_array(source, len, 4)?;
+                NbtTag::IntArray(Vec::new())
+            } else {
+                let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
 
-            NbtTag::IntArray(array)
+                for _ in 0 .. len {
+                    array.push(source.read_i32::<BigEndian>()?);
+                }
+
+                NbtTag::IntArray(array)
+            }
         }
         0xC => {
             let len = source.read_i32::<BigEndian>()? as usize;
-            let mut array = vec![0_i64; len];
+            check_len(len, opts)?;
 
-            for i in 0 .. len {
-                array[i] = source.read_i64::<BigEndian>()?;
-            }
+            if opts.skip_arrays {
+                skip_array(source, len, 8)?;
+                NbtTag::LongArray(Vec::new())
+            } else {
+                let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
 
-            NbtTag::LongArray(array)
+                for _ in 0 .. len {
+                    array.push(source.read_i64::<BigEndian>()?);
+                }
+
+                NbtTag::LongArray(array)
+            }
         }
         _ =>
             return Err(Error::new(
@@ -119,7 +247,20 @@ fn read_tag_body<R: Read>(source: &mut R, id: u8) -> Result<NbtTag> {
     Ok(tag)
 }
 
-fn read_string<R: Read>(source: &mut R) -> Result<String> {
+// Returns the incremented depth, or an error if doing so would exceed the configured maximum.
+// Called once per compound/list body so that recursion into their children is what's bounded.
+pub(crate) fn check_depth(depth: usize, opts: &ReadOptions) -> Result<usize> {
+    let depth = depth + 1;
+    if depth > opts.max_depth {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "maximum NBT nesting depth exceeded",
+        ));
+    }
+    Ok(depth)
+}
+
+pub(crate) fn read_string<R: Read>(source: &mut R) -> Result<String> {
     let len = source.read_u16::<BigEndian>()? as usize;
     let mut bytes = vec![0; len];
     source.read_exact(&mut bytes)?;
@@ -135,3 +276,57 @@ fn read_string<R: Read>(source: &mut R) -> Result<String> {
 
     Ok(java_decoded.into_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{deeply_nested_compound, hostile_byte_array};
+
+    #[test]
+    fn rejects_oversized_array_length_instead_of_allocating() {
+        let data = hostile_byte_array();
+        assert!(read_nbt_uncompressed(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_nesting_past_max_depth() {
+        let data = deeply_nested_compound(ReadOptions::default().max_depth + 1);
+        let err = read_nbt_uncompressed(&mut &data[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn skip_arrays_leaves_an_empty_array_in_place() {
+        let mut data = vec![0xA]; // root TAG_Compound
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+        data.push(0xB); // TAG_Int_Array field
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty field name
+        data.extend_from_slice(&3_i32.to_be_bytes()); // claimed length
+        data.extend_from_slice(&1_i32.to_be_bytes());
+        data.extend_from_slice(&2_i32.to_be_bytes());
+        data.extend_from_slice(&3_i32.to_be_bytes());
+        data.push(0x0); // TAG_End
+
+        let opts = ReadOptions {
+            skip_arrays: true,
+            ..ReadOptions::default()
+        };
+        let (compound, _) = read_nbt_uncompressed_with_opts(&mut &data[..], opts).unwrap();
+        assert_eq!(compound.get(""), Some(&NbtTag::IntArray(Vec::new())));
+    }
+
+    #[test]
+    fn reads_a_small_document() {
+        let mut data = vec![0xA]; // root TAG_Compound
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+        data.push(0x3); // TAG_Int field
+        data.extend_from_slice(&1_u16.to_be_bytes());
+        data.extend_from_slice(b"x");
+        data.extend_from_slice(&42_i32.to_be_bytes());
+        data.push(0x0); // TAG_End
+
+        let (compound, root_name) = read_nbt_uncompressed(&mut &data[..]).unwrap();
+        assert_eq!(root_name, "");
+        assert_eq!(compound.get("x"), Some(&NbtTag::Int(42)));
+    }
+}
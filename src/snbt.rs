@@ -0,0 +1,561 @@
+use crate::{NbtCompound, NbtList, NbtTag};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// An error encountered while parsing stringified NBT (SNBT), as produced by [`parse_snbt`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnbtError {
+    /// The input ended before a complete tag could be parsed.
+    UnexpectedEof,
+    /// An unexpected character was encountered at the given byte offset.
+    UnexpectedChar(usize, char),
+    /// The numeric literal at the given byte offset could not be parsed.
+    InvalidNumber(usize, String),
+    /// Trailing, non-whitespace data was found after the root tag ended.
+    TrailingData(usize),
+    /// The input nested compounds/lists more deeply than [`MAX_DEPTH`] allows.
+    MaxDepthExceeded,
+}
+
+impl Display for SnbtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SnbtError::UnexpectedEof => write!(f, "unexpected end of input"),
+            SnbtError::UnexpectedChar(pos, c) =>
+                write!(f, "unexpected character '{}' at byte {}", c, pos),
+            SnbtError::InvalidNumber(pos, lit) =>
+                write!(f, "invalid numeric literal \"{}\" at byte {}", lit, pos),
+            SnbtError::TrailingData(pos) => write!(f, "trailing data at byte {}", pos),
+            SnbtError::MaxDepthExceeded =>
+                write!(f, "exceeded the maximum nesting depth ({})", MAX_DEPTH),
+        }
+    }
+}
+
+impl Error for SnbtError {}
+
+// Bounds how deeply compounds and lists may nest while parsing. Like `ReadOptions::max_depth` for
+// the binary reader, this exists so a crafted SNBT string nested deeply enough (`[[[[...]]]]`)
+// fails with an error instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+/// Parses a stringified NBT (SNBT) compound, the textual form used by Minecraft commands and
+/// data packs (e.g. `{name:"Bananrama",count:3b,items:[I;1,2,3]}`), into an [`NbtCompound`].
+pub fn parse_snbt(snbt: &str) -> Result<NbtCompound, SnbtError> {
+    let mut parser = Parser::new(snbt);
+    parser.skip_whitespace();
+    let start = parser.peek();
+    let tag = parser.parse_tag()?;
+    parser.skip_whitespace();
+    if let Some((pos, _)) = parser.peek() {
+        return Err(SnbtError::TrailingData(pos));
+    }
+
+    match (tag, start) {
+        (NbtTag::Compound(compound), _) => Ok(compound),
+        (_, Some((pos, c))) => Err(SnbtError::UnexpectedChar(pos, c)),
+        (_, None) => Err(SnbtError::UnexpectedEof),
+    }
+}
+
+impl NbtTag {
+    /// Converts this tag to its stringified NBT (SNBT) representation.
+    pub fn to_snbt(&self) -> String {
+        let mut out = String::new();
+        write_tag_snbt(self, &mut out);
+        out
+    }
+}
+
+fn write_tag_snbt(tag: &NbtTag, out: &mut String) {
+    match tag {
+        NbtTag::Byte(value) => out.push_str(&format!("{}b", value)),
+        NbtTag::Short(value) => out.push_str(&format!("{}s", value)),
+        NbtTag::Int(value) => out.push_str(&value.to_string()),
+        NbtTag::Long(value) => out.push_str(&format!("{}L", value)),
+        NbtTag::Float(value) => out.push_str(&format_f32(*value)),
+        NbtTag::Double(value) => out.push_str(&format_f64(*value)),
+        NbtTag::ByteArray(array) => write_array_snbt(out, 'B', array.iter()),
+        NbtTag::String(value) => write_quoted_string(value, out),
+        NbtTag::List(list) => write_list_snbt(list, out),
+        NbtTag::Compound(compound) => write_compound_snbt(compound, out),
+        NbtTag::IntArray(array) => write_array_snbt(out, 'I', array.iter()),
+        NbtTag::LongArray(array) => write_array_snbt(out, 'L', array.iter()),
+    }
+}
+
+// `{}` on a NaN/infinite float prints "NaN"/"inf"/"-inf", none of which `looks_numeric` accepts as
+// a suffixed literal's body, so those would otherwise come back from `parse_snbt` as a plain
+// string instead of the original float. Spelled out as "NaN"/"Infinity"/"-Infinity" instead, which
+// `parse_number_literal` special-cases (and `f32`/`f64`'s `FromStr` already accepts) so the value
+// round-trips.
+fn format_f32(value: f32) -> String {
+    if value.is_nan() {
+        "NaNf".to_owned()
+    } else if value.is_infinite() {
+        format!("{}Infinityf", if value.is_sign_negative() { "-" } else { "" })
+    } else {
+        format!("{}f", value)
+    }
+}
+
+fn format_f64(value: f64) -> String {
+    if value.is_nan() {
+        "NaNd".to_owned()
+    } else if value.is_infinite() {
+        format!("{}Infinityd", if value.is_sign_negative() { "-" } else { "" })
+    } else {
+        format!("{}d", value)
+    }
+}
+
+fn write_array_snbt<T: Display>(out: &mut String, prefix: char, values: impl Iterator<Item = T>) {
+    out.push('[');
+    out.push(prefix);
+    out.push(';');
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&value.to_string());
+    }
+    out.push(']');
+}
+
+fn write_list_snbt(list: &NbtList, out: &mut String) {
+    out.push('[');
+    for (i, tag) in list.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_tag_snbt(tag, out);
+    }
+    out.push(']');
+}
+
+fn write_compound_snbt(compound: &NbtCompound, out: &mut String) {
+    out.push('{');
+    for (i, (key, tag)) in compound.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if is_unquoted_key(key) {
+            out.push_str(key);
+        } else {
+            write_quoted_string(key, out);
+        }
+        out.push(':');
+        write_tag_snbt(tag, out);
+    }
+    out.push('}');
+}
+
+fn is_unquoted_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+')
+}
+
+fn write_quoted_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// A small recursive-descent tokenizer over the raw SNBT string. It tracks a byte offset for
+// error reporting, but otherwise just walks the `char_indices` iterator one token at a time.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            chars: source.char_indices().peekable(),
+            depth: 0,
+        }
+    }
+
+    // Increments the nesting depth, failing once `MAX_DEPTH` is exceeded, and always decrements
+    // it back afterward so sibling (non-nested) compounds/lists don't accumulate depth.
+    fn with_nesting<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, SnbtError>) -> Result<T, SnbtError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            self.depth -= 1;
+            return Err(SnbtError::MaxDepthExceeded);
+        }
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&mut self) -> Option<(usize, char)> {
+        self.chars.peek().copied()
+    }
+
+    fn advance(&mut self) -> Option<(usize, char)> {
+        self.chars.next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((_, c)) = self.peek() {
+            if c.is_whitespace() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SnbtError> {
+        match self.advance() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((pos, c)) => Err(SnbtError::UnexpectedChar(pos, c)),
+            None => Err(SnbtError::UnexpectedEof),
+        }
+    }
+
+    fn parse_tag(&mut self) -> Result<NbtTag, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some((_, '{')) => self.with_nesting(|p| p.parse_compound()).map(NbtTag::Compound),
+            Some((_, '[')) => self.with_nesting(|p| p.parse_bracketed()),
+            Some((_, '"')) => self.parse_quoted_string().map(NbtTag::String),
+            Some((pos, _)) => self.parse_unquoted(pos),
+            None => Err(SnbtError::UnexpectedEof),
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<NbtCompound, SnbtError> {
+        self.expect('{')?;
+        let mut compound = NbtCompound::new();
+
+        self.skip_whitespace();
+        if let Some((_, '}')) = self.peek() {
+            self.advance();
+            return Ok(compound);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some((_, '"')) => self.parse_quoted_string()?,
+                Some((pos, _)) => self.parse_unquoted_key(pos)?,
+                None => return Err(SnbtError::UnexpectedEof),
+            };
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_tag()?;
+            compound.insert(key, value);
+
+            self.skip_whitespace();
+            match self.advance() {
+                Some((_, ',')) => continue,
+                Some((_, '}')) => break,
+                Some((pos, c)) => return Err(SnbtError::UnexpectedChar(pos, c)),
+                None => return Err(SnbtError::UnexpectedEof),
+            }
+        }
+
+        Ok(compound)
+    }
+
+    // Handles both `[tag,tag,...]` lists and `[B;...]`/`[I;...]`/`[L;...]` primitive arrays, which
+    // share the same opening bracket and are only distinguished by the type prefix.
+    fn parse_bracketed(&mut self) -> Result<NbtTag, SnbtError> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        if let Some((_, prefix @ ('B' | 'I' | 'L'))) = self.peek() {
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            if let Some((_, ';')) = lookahead.next() {
+                self.advance();
+                self.advance();
+                return self.parse_array(prefix);
+            }
+        }
+
+        let mut list = NbtList::new();
+        self.skip_whitespace();
+        if let Some((_, ']')) = self.peek() {
+            self.advance();
+            return Ok(NbtTag::List(list));
+        }
+
+        loop {
+            let value = self.parse_tag()?;
+            list.push(value);
+
+            self.skip_whitespace();
+            match self.advance() {
+                Some((_, ',')) => {
+                    self.skip_whitespace();
+                    continue;
+                }
+                Some((_, ']')) => break,
+                Some((pos, c)) => return Err(SnbtError::UnexpectedChar(pos, c)),
+                None => return Err(SnbtError::UnexpectedEof),
+            }
+        }
+
+        Ok(NbtTag::List(list))
+    }
+
+    fn parse_array(&mut self, prefix: char) -> Result<NbtTag, SnbtError> {
+        let mut literals = Vec::new();
+
+        self.skip_whitespace();
+        if let Some((_, ']')) = self.peek() {
+            self.advance();
+        } else {
+            loop {
+                self.skip_whitespace();
+                let (pos, literal) = self.read_raw_literal()?;
+                literals.push((pos, literal));
+
+                self.skip_whitespace();
+                match self.advance() {
+                    Some((_, ',')) => continue,
+                    Some((_, ']')) => break,
+                    Some((pos, c)) => return Err(SnbtError::UnexpectedChar(pos, c)),
+                    None => return Err(SnbtError::UnexpectedEof),
+                }
+            }
+        }
+
+        match prefix {
+            'B' => {
+                let values = literals
+                    .into_iter()
+                    .map(|(pos, lit)| parse_int_literal::<i8>(pos, &lit))
+                    .collect::<Result<_, _>>()?;
+                Ok(NbtTag::ByteArray(values))
+            }
+            'I' => {
+                let values = literals
+                    .into_iter()
+                    .map(|(pos, lit)| parse_int_literal::<i32>(pos, &lit))
+                    .collect::<Result<_, _>>()?;
+                Ok(NbtTag::IntArray(values))
+            }
+            'L' => {
+                let values = literals
+                    .into_iter()
+                    .map(|(pos, lit)| parse_int_literal::<i64>(pos, &lit))
+                    .collect::<Result<_, _>>()?;
+                Ok(NbtTag::LongArray(values))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<String, SnbtError> {
+        self.expect('"')?;
+        let mut result = String::new();
+
+        loop {
+            match self.advance() {
+                Some((_, '"')) => break,
+                Some((_, '\\')) => match self.advance() {
+                    Some((_, '"')) => result.push('"'),
+                    Some((_, '\\')) => result.push('\\'),
+                    Some((pos, c)) => return Err(SnbtError::UnexpectedChar(pos, c)),
+                    None => return Err(SnbtError::UnexpectedEof),
+                },
+                Some((_, c)) => result.push(c),
+                None => return Err(SnbtError::UnexpectedEof),
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn parse_unquoted_key(&mut self, start: usize) -> Result<String, SnbtError> {
+        let (_, literal) = self.read_raw_literal()?;
+        if literal.is_empty() {
+            return match self.peek() {
+                Some((_, c)) => Err(SnbtError::UnexpectedChar(start, c)),
+                None => Err(SnbtError::UnexpectedEof),
+            };
+        }
+        Ok(literal)
+    }
+
+    // Reads a run of characters that make up an unquoted key or number literal: anything other
+    // than whitespace or one of the structural delimiters.
+    fn read_raw_literal(&mut self) -> Result<(usize, String), SnbtError> {
+        let start = match self.peek() {
+            Some((pos, _)) => pos,
+            None => return Err(SnbtError::UnexpectedEof),
+        };
+
+        let mut literal = String::new();
+        while let Some((_, c)) = self.peek() {
+            if c.is_whitespace() || matches!(c, ',' | ':' | '{' | '}' | '[' | ']') {
+                break;
+            }
+            literal.push(c);
+            self.advance();
+        }
+
+        Ok((start, literal))
+    }
+
+    fn parse_unquoted(&mut self, start: usize) -> Result<NbtTag, SnbtError> {
+        let (pos, literal) = self.read_raw_literal()?;
+        if literal.is_empty() {
+            return match self.advance() {
+                Some((pos, c)) => Err(SnbtError::UnexpectedChar(pos, c)),
+                None => Err(SnbtError::UnexpectedEof),
+            };
+        }
+
+        let _ = start;
+        match parse_number_literal(pos, &literal)? {
+            Some(tag) => Ok(tag),
+            // Not every number-suffix; most unquoted tokens in real SNBT (`stone`, `minecraft:air`)
+            // are plain strings, so a literal that doesn't even look like a number falls back to one.
+            None => Ok(NbtTag::String(literal)),
+        }
+    }
+}
+
+// Returns `Ok(None)` for a literal that doesn't look like a number at all (so the caller can fall
+// back to treating it as a plain string), but returns `Err` for a literal that has an explicit
+// type suffix (`b`/`s`/`l`/`f`/`d`) and looks numeric yet fails to parse as that type (e.g. `300b`,
+// out of `i8` range) -- that's a typo in an intentional numeric literal, not a string.
+fn parse_number_literal(pos: usize, literal: &str) -> Result<Option<NbtTag>, SnbtError> {
+    let lower = literal.to_ascii_lowercase();
+    // Split on the last `char`, not the last byte: an unquoted literal can end in a multi-byte
+    // character (it's only excluded from containing whitespace or structural delimiters), and
+    // slicing at `len - 1` there would land inside that character and panic.
+    let last_len = match lower.chars().next_back() {
+        Some(c) => c.len_utf8(),
+        None => return Ok(None),
+    };
+    let (body, suffix) = lower.split_at(lower.len() - last_len);
+
+    let typed = match suffix {
+        "b" if literal.len() > 1 && looks_numeric(body) =>
+            Some(parse_int_literal::<i8>(pos, body).map(NbtTag::Byte)),
+        "s" if looks_numeric(body) =>
+            Some(parse_int_literal::<i16>(pos, body).map(NbtTag::Short)),
+        "l" if looks_numeric(body) =>
+            Some(parse_int_literal::<i64>(pos, body).map(NbtTag::Long)),
+        "f" if looks_numeric(body) || looks_non_finite(body) =>
+            Some(parse_float_literal::<f32>(pos, body).map(NbtTag::Float)),
+        "d" if looks_numeric(body) || looks_non_finite(body) =>
+            Some(parse_float_literal::<f64>(pos, body).map(NbtTag::Double)),
+        _ => None,
+    };
+
+    if let Some(result) = typed {
+        return result.map(Some);
+    }
+
+    Ok(if literal.contains('.') {
+        parse_float_literal::<f64>(pos, literal).ok().map(NbtTag::Double)
+    } else {
+        parse_int_literal::<i32>(pos, literal).ok().map(NbtTag::Int)
+    })
+}
+
+// Whether `s` starts (after an optional leading `-`) with an ASCII digit, used to tell a
+// genuine-but-invalid numeric literal (`300b`) apart from a word that merely ends in a suffix
+// letter (`cab`).
+fn looks_numeric(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    s.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+// Whether `s` (already lowercased by the caller) is "nan" or "infinity", optionally negative --
+// the bodies `format_f32`/`format_f64` emit for non-finite values, alongside the digit-led bodies
+// `looks_numeric` covers.
+fn looks_non_finite(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    s == "nan" || s == "infinity"
+}
+
+fn parse_int_literal<T: std::str::FromStr>(pos: usize, literal: &str) -> Result<T, SnbtError> {
+    literal
+        .parse()
+        .map_err(|_| SnbtError::InvalidNumber(pos, literal.to_owned()))
+}
+
+fn parse_float_literal<T: std::str::FromStr>(pos: usize, literal: &str) -> Result<T, SnbtError> {
+    literal
+        .parse()
+        .map_err(|_| SnbtError::InvalidNumber(pos, literal.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_compound() {
+        let snbt = r#"{name:"Bananrama",count:3b,items:[1,2,3],ids:[I;1,2,3]}"#;
+        let compound = parse_snbt(snbt).unwrap();
+        assert_eq!(compound.get("name"), Some(&NbtTag::String("Bananrama".to_owned())));
+        assert_eq!(compound.get("count"), Some(&NbtTag::Byte(3)));
+
+        let reparsed = parse_snbt(&NbtTag::Compound(compound).to_snbt()).unwrap();
+        assert_eq!(reparsed.get("name"), Some(&NbtTag::String("Bananrama".to_owned())));
+        assert_eq!(reparsed.get("count"), Some(&NbtTag::Byte(3)));
+    }
+
+    #[test]
+    fn rejects_unterminated_input() {
+        assert_eq!(parse_snbt("{name:"), Err(SnbtError::UnexpectedEof));
+    }
+
+    #[test]
+    fn rejects_out_of_range_suffixed_numeric_literal() {
+        assert!(matches!(
+            parse_snbt("{x:300b}"),
+            Err(SnbtError::InvalidNumber(_, _))
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_string_for_non_numeric_unquoted_tokens() {
+        let compound = parse_snbt("{a:stone,b:cab,c:3b}").unwrap();
+        assert_eq!(compound.get("a"), Some(&NbtTag::String("stone".to_owned())));
+        assert_eq!(compound.get("b"), Some(&NbtTag::String("cab".to_owned())));
+        assert_eq!(compound.get("c"), Some(&NbtTag::Byte(3)));
+    }
+
+    // A chain of unclosed nested lists, one level deeper than `MAX_DEPTH`: the depth bound must
+    // reject it before the parser reaches the end of the input (which would be `UnexpectedEof`).
+    #[test]
+    fn rejects_nesting_past_max_depth() {
+        let snbt = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(parse_snbt(&snbt), Err(SnbtError::MaxDepthExceeded));
+    }
+
+    #[test]
+    fn round_trips_non_finite_floats() {
+        let mut compound = NbtCompound::new();
+        compound.insert("nan", NbtTag::Double(f64::NAN));
+        compound.insert("inf", NbtTag::Double(f64::INFINITY));
+        compound.insert("neg_inf", NbtTag::Float(f32::NEG_INFINITY));
+
+        let reparsed = parse_snbt(&NbtTag::Compound(compound).to_snbt()).unwrap();
+
+        assert!(matches!(reparsed.get("nan"), Some(NbtTag::Double(v)) if v.is_nan()));
+        assert_eq!(reparsed.get("inf"), Some(&NbtTag::Double(f64::INFINITY)));
+        assert_eq!(reparsed.get("neg_inf"), Some(&NbtTag::Float(f32::NEG_INFINITY)));
+    }
+}
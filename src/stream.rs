@@ -0,0 +1,324 @@
+use crate::read::{check_depth, check_len, read_string, skip_array, ReadOptions, PREALLOCATE_CAP};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::{Error, ErrorKind, Read, Result};
+
+/// A shallow event describing a single piece of NBT structure encountered while scanning a binary
+/// NBT source with [`NbtStreamParser`].
+///
+/// Unlike the tree produced by [`read_nbt_uncompressed`](crate::read_nbt_uncompressed), these
+/// events are emitted incrementally without ever materializing a full [`NbtCompound`](crate::NbtCompound)
+/// or [`NbtList`](crate::NbtList), which makes this suitable for scanning large files for structure
+/// or for extracting a handful of fields without the cost of building the whole tree.
+///
+/// The `Option<String>` carried by each variant is the tag's name: it is `Some` when the tag is a
+/// direct child of a compound, and `None` when the tag is an element of a list, since list elements
+/// are unnamed in the binary format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NbtEvent {
+    /// The start of a compound tag with the given name.
+    Compound(Option<String>),
+    /// The end of the most recently opened compound.
+    CompoundEnd,
+    /// The start of a list tag: its name, the type id of its elements, and its element count.
+    List(Option<String>, u8, i32),
+    /// The end of the most recently opened list.
+    ListEnd,
+    /// A `TAG_Byte`.
+    Byte(Option<String>, i8),
+    /// A `TAG_Short`.
+    Short(Option<String>, i16),
+    /// A `TAG_Int`.
+    Int(Option<String>, i32),
+    /// A `TAG_Long`.
+    Long(Option<String>, i64),
+    /// A `TAG_Float`.
+    Float(Option<String>, f32),
+    /// A `TAG_Double`.
+    Double(Option<String>, f64),
+    /// A `TAG_Byte_Array`.
+    ByteArray(Option<String>, Vec<i8>),
+    /// A `TAG_String`.
+    String(Option<String>, String),
+    /// A `TAG_Int_Array`.
+    IntArray(Option<String>, Vec<i32>),
+    /// A `TAG_Long_Array`.
+    LongArray(Option<String>, Vec<i64>),
+}
+
+// Tracks the container the parser is currently emitting elements for so that `next` never has to
+// recurse: a compound asks the source for an id byte before each child, while a list already knows
+// the type and remaining count of its elements.
+enum Context {
+    Compound,
+    List { remaining: i32, element_id: u8 },
+}
+
+/// A pull-based parser which reads a binary NBT source one shallow [`NbtEvent`] at a time instead
+/// of materializing the full tag tree up front.
+///
+/// This is useful when a caller only wants to scan the structure of large NBT data (such as a
+/// region file) or extract a few fields, since it avoids allocating the `NbtCompound`/`NbtList`
+/// tree for data the caller doesn't care about. List and array lengths, and the nesting depth of
+/// compounds and lists, are still read directly from attacker-controlled input, so this parser
+/// observes the same [`ReadOptions`] as
+/// [`read_nbt_uncompressed_with_opts`](crate::read_nbt_uncompressed_with_opts): a malicious
+/// length can't force an oversized allocation here, and a malicious nesting depth is rejected at
+/// the same `max_depth` a caller who reconstructs a tree from these events would otherwise
+/// overflow their own stack on.
+pub struct NbtStreamParser<R> {
+    source: R,
+    opts: ReadOptions,
+    stack: Vec<Context>,
+    depth: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> NbtStreamParser<R> {
+    /// Creates a new stream parser which reads events from the given source, using default
+    /// [`ReadOptions`].
+    ///
+    /// The source is expected to contain uncompressed binary NBT data starting with a root
+    /// compound, the same format accepted by [`read_nbt_uncompressed`](crate::read_nbt_uncompressed).
+    pub fn new(source: R) -> Self {
+        Self::new_with_opts(source, ReadOptions::default())
+    }
+
+    /// Creates a new stream parser which reads events from the given source, observing the given
+    /// [`ReadOptions`].
+    pub fn new_with_opts(source: R, opts: ReadOptions) -> Self {
+        NbtStreamParser {
+            source,
+            opts,
+            stack: Vec::new(),
+            depth: 0,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Reads and returns the next event from the source, or `None` once the root compound has
+    /// been fully emitted.
+    // Not an `Iterator` impl: reading can fail mid-stream, and `Iterator::next` can't express a
+    // fallible `Option`, so this returns `Result<Option<NbtEvent>>` directly instead.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<NbtEvent>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        if !self.started {
+            self.started = true;
+
+            let root_id = self.source.read_u8()?;
+            if root_id != 0xA {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "NBT data does not start with a compound type.",
+                ));
+            }
+
+            let root_name = read_string(&mut self.source)?;
+            self.depth = check_depth(self.depth, &self.opts)?;
+            self.stack.push(Context::Compound);
+            return Ok(Some(NbtEvent::Compound(Some(root_name))));
+        }
+
+        let (name, id) = match self.stack.last_mut() {
+            None => {
+                self.done = true;
+                return Ok(None);
+            }
+            Some(Context::List {
+                remaining,
+                element_id,
+            }) =>
+                if *remaining == 0 {
+                    self.stack.pop();
+                    self.depth -= 1;
+                    return Ok(Some(NbtEvent::ListEnd));
+                } else {
+                    *remaining -= 1;
+                    (None, *element_id)
+                },
+            Some(Context::Compound) => {
+                let id = self.source.read_u8()?;
+                if id == 0x0 {
+                    self.stack.pop();
+                    self.depth -= 1;
+                    if self.stack.is_empty() {
+                        self.done = true;
+                    }
+                    return Ok(Some(NbtEvent::CompoundEnd));
+                }
+
+                let name = read_string(&mut self.source)?;
+                (Some(name), id)
+            }
+        };
+
+        self.read_event(name, id)
+    }
+
+    fn read_event(&mut self, name: Option<String>, id: u8) -> Result<Option<NbtEvent>> {
+        let event = match id {
+            0x1 => NbtEvent::Byte(name, self.source.read_i8()?),
+            0x2 => NbtEvent::Short(name, self.source.read_i16::<BigEndian>()?),
+            0x3 => NbtEvent::Int(name, self.source.read_i32::<BigEndian>()?),
+            0x4 => NbtEvent::Long(name, self.source.read_i64::<BigEndian>()?),
+            0x5 => NbtEvent::Float(name, self.source.read_f32::<BigEndian>()?),
+            0x6 => NbtEvent::Double(name, self.source.read_f64::<BigEndian>()?),
+            0x7 => {
+                let len = self.source.read_i32::<BigEndian>()? as usize;
+                check_len(len, &self.opts)?;
+
+                if self.opts.skip_arrays {
+                    skip_array(&mut self.source, len, 1)?;
+                    NbtEvent::ByteArray(name, Vec::new())
+                } else {
+                    let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
+                    for _ in 0 .. len {
+                        array.push(self.source.read_i8()?);
+                    }
+                    NbtEvent::ByteArray(name, array)
+                }
+            }
+            0x8 => NbtEvent::String(name, read_string(&mut self.source)?),
+            0x9 => {
+                let element_id = self.source.read_u8()?;
+                let len = self.source.read_i32::<BigEndian>()?;
+
+                if element_id > 0xC || (element_id == 0 && len > 0) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Invalid list type encountered.",
+                    ));
+                }
+
+                // `len` is trusted as a non-negative element count from here on (stored in
+                // `Context::List::remaining` and decremented once per emitted element), so it must
+                // be bounds-checked the same way a tree-reader list length is: a negative `len`
+                // sign-extends to a huge `usize` here and gets rejected by the same max check.
+                check_len(len as usize, &self.opts)?;
+                self.depth = check_depth(self.depth, &self.opts)?;
+
+                self.stack.push(Context::List {
+                    remaining: len,
+                    element_id,
+                });
+
+                NbtEvent::List(name, element_id, len)
+            }
+            0xA => {
+                self.depth = check_depth(self.depth, &self.opts)?;
+                self.stack.push(Context::Compound);
+                NbtEvent::Compound(name)
+            }
+            0xB => {
+                let len = self.source.read_i32::<BigEndian>()? as usize;
+                check_len(len, &self.opts)?;
+
+                if self.opts.skip_arrays {
+                    skip_array(&mut self.source, len, 4)?;
+                    NbtEvent::IntArray(name, Vec::new())
+                } else {
+                    let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
+                    for _ in 0 .. len {
+                        array.push(self.source.read_i32::<BigEndian>()?);
+                    }
+                    NbtEvent::IntArray(name, array)
+                }
+            }
+            0xC => {
+                let len = self.source.read_i32::<BigEndian>()? as usize;
+                check_len(len, &self.opts)?;
+
+                if self.opts.skip_arrays {
+                    skip_array(&mut self.source, len, 8)?;
+                    NbtEvent::LongArray(name, Vec::new())
+                } else {
+                    let mut array = Vec::with_capacity(len.min(PREALLOCATE_CAP));
+                    for _ in 0 .. len {
+                        array.push(self.source.read_i64::<BigEndian>()?);
+                    }
+                    NbtEvent::LongArray(name, array)
+                }
+            }
+            _ =>
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Invalid tag type encountered.",
+                )),
+        };
+
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{deeply_nested_compound, hostile_byte_array};
+
+    #[test]
+    fn rejects_oversized_array_length_instead_of_allocating() {
+        let data = hostile_byte_array();
+        let mut parser = NbtStreamParser::new(&data[..]);
+        assert!(matches!(parser.next(), Ok(Some(NbtEvent::Compound(_)))));
+        assert!(parser.next().is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_list_length() {
+        let mut data = vec![0xA]; // root TAG_Compound
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+        data.push(0x9); // TAG_List field
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty field name
+        data.push(0x3); // element type: TAG_Int
+        data.extend_from_slice(&i32::MAX.to_be_bytes()); // claimed length
+
+        let mut parser = NbtStreamParser::new(&data[..]);
+        assert!(matches!(parser.next(), Ok(Some(NbtEvent::Compound(_)))));
+        assert!(parser.next().is_err());
+    }
+
+    #[test]
+    fn rejects_nesting_past_max_depth() {
+        let data = deeply_nested_compound(ReadOptions::default().max_depth + 1);
+        let mut parser = NbtStreamParser::new(&data[..]);
+        loop {
+            match parser.next() {
+                Ok(Some(_)) => continue,
+                Ok(None) => panic!("expected an error before the root compound closed"),
+                Err(e) => {
+                    assert_eq!(e.kind(), ErrorKind::InvalidData);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn emits_events_for_a_small_document() {
+        let mut data = vec![0xA]; // root TAG_Compound
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+        data.push(0x3); // TAG_Int field
+        write_name(&mut data, "x");
+        data.extend_from_slice(&42_i32.to_be_bytes());
+        data.push(0x0); // TAG_End
+
+        let mut parser = NbtStreamParser::new(&data[..]);
+        assert_eq!(parser.next().unwrap(), Some(NbtEvent::Compound(Some(String::new()))));
+        assert_eq!(
+            parser.next().unwrap(),
+            Some(NbtEvent::Int(Some("x".to_owned()), 42))
+        );
+        assert_eq!(parser.next().unwrap(), Some(NbtEvent::CompoundEnd));
+        assert_eq!(parser.next().unwrap(), None);
+    }
+
+    fn write_name(data: &mut Vec<u8>, name: &str) {
+        data.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        data.extend_from_slice(name.as_bytes());
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::HashMap;
+
+#[cfg(feature = "preserve_order")]
+use indexmap::IndexMap;
+
+#[cfg(not(feature = "preserve_order"))]
+type Map = HashMap<String, NbtTag>;
+#[cfg(feature = "preserve_order")]
+type Map = IndexMap<String, NbtTag>;
+
+/// A complete NBT tag, holding a nameless value of one of the twelve NBT types. Names are stored
+/// alongside a tag only when it's a direct child of a [`NbtCompound`], or implicitly by position
+/// when it's an element of a [`NbtList`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(NbtList),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// A map of named NBT tags, corresponding to `TAG_Compound`.
+///
+/// The backing storage is a [`HashMap`] by default, so iteration order (and therefore the field
+/// order produced by [`to_snbt`](NbtTag::to_snbt) and
+/// [`write_nbt_uncompressed`](crate::write_nbt_uncompressed)) is not guaranteed to match insertion
+/// order. Enabling the `preserve_order` feature swaps the backing storage for an
+/// [`IndexMap`](indexmap::IndexMap) instead, so that iteration reproduces insertion order; the
+/// rest of this API is unaffected by the feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NbtCompound(Map);
+
+impl NbtCompound {
+    /// Creates a new, empty compound.
+    pub fn new() -> Self {
+        NbtCompound(Map::new())
+    }
+
+    /// Creates a new, empty compound with capacity for at least `capacity` tags without
+    /// reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        NbtCompound(Map::with_capacity(capacity))
+    }
+
+    /// Inserts the given tag under the given name, overwriting any tag already present under
+    /// that name.
+    pub fn insert<S: Into<String>>(&mut self, name: S, tag: NbtTag) {
+        self.0.insert(name.into(), tag);
+    }
+
+    /// Returns a reference to the tag under the given name, if present.
+    pub fn get(&self, name: &str) -> Option<&NbtTag> {
+        self.0.get(name)
+    }
+
+    /// Returns `true` if this compound contains a tag under the given name.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Returns the number of tags in this compound.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if this compound contains no tags.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the name-tag pairs in this compound, in the order determined by
+    /// the backing storage (insertion order when the `preserve_order` feature is enabled).
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &NbtTag)> {
+        self.0.iter()
+    }
+}
+
+/// An ordered list of unnamed NBT tags, corresponding to `TAG_List`. Elements always retain
+/// insertion order, regardless of the `preserve_order` feature, since they're backed by a `Vec`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NbtList(Vec<NbtTag>);
+
+impl NbtList {
+    /// Creates a new, empty list.
+    pub fn new() -> Self {
+        NbtList(Vec::new())
+    }
+
+    /// Creates a new, empty list with capacity for at least `capacity` tags without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        NbtList(Vec::with_capacity(capacity))
+    }
+
+    /// Appends a tag to the end of this list.
+    pub fn push(&mut self, tag: NbtTag) {
+        self.0.push(tag);
+    }
+
+    /// Returns the number of tags in this list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if this list contains no tags.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the tags in this list, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &NbtTag> {
+        self.0.iter()
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserve_order_feature_retains_insertion_order() {
+        let mut compound = NbtCompound::new();
+        compound.insert("z", NbtTag::Int(1));
+        compound.insert("a", NbtTag::Int(2));
+        compound.insert("m", NbtTag::Int(3));
+
+        let names: Vec<&str> = compound.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["z", "a", "m"]);
+    }
+
+    #[test]
+    fn preserve_order_survives_an_snbt_round_trip() {
+        use crate::snbt::parse_snbt;
+
+        let mut compound = NbtCompound::new();
+        compound.insert("z", NbtTag::Int(1));
+        compound.insert("a", NbtTag::Int(2));
+        compound.insert("m", NbtTag::Int(3));
+
+        let snbt = NbtTag::Compound(compound).to_snbt();
+        let reparsed = parse_snbt(&snbt).unwrap();
+
+        let names: Vec<&str> = reparsed.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["z", "a", "m"]);
+    }
+
+    #[test]
+    fn preserve_order_survives_a_binary_round_trip() {
+        use crate::{read_nbt_uncompressed, write_nbt_uncompressed};
+
+        let mut compound = NbtCompound::new();
+        compound.insert("z", NbtTag::Int(1));
+        compound.insert("a", NbtTag::Int(2));
+        compound.insert("m", NbtTag::Int(3));
+
+        let mut bytes = Vec::new();
+        write_nbt_uncompressed(&mut bytes, "", &compound).unwrap();
+        let (reparsed, _) = read_nbt_uncompressed(&mut &bytes[..]).unwrap();
+
+        let names: Vec<&str> = reparsed.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["z", "a", "m"]);
+    }
+}
@@ -0,0 +1,28 @@
+//! Binary NBT fixtures shared by the `read` and `stream` test modules, which both need to exercise
+//! the same hostile input against their respective readers.
+
+// A root compound containing a TAG_Byte_Array field claiming `len = i32::MAX`, then EOF: with no
+// bound on `len` this used to eagerly allocate an ~2 GiB buffer before a single payload byte was
+// read.
+pub(crate) fn hostile_byte_array() -> Vec<u8> {
+    let mut data = vec![0xA]; // root TAG_Compound
+    data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+    data.push(0x7); // TAG_Byte_Array field
+    data.extend_from_slice(&0_u16.to_be_bytes()); // empty field name
+    data.extend_from_slice(&i32::MAX.to_be_bytes()); // claimed length
+    data
+}
+
+// A root TAG_Compound followed by a chain of `depth` nested TAG_Compounds (`depth + 1` compounds in
+// all), each containing the next as its only field, with no terminating TAG_End. A reader whose
+// `max_depth` is below `depth + 1` fails with `InvalidData` before reaching the end of the data;
+// one without a depth bound descends through every level and fails with `UnexpectedEof` instead.
+pub(crate) fn deeply_nested_compound(depth: usize) -> Vec<u8> {
+    let mut data = vec![0xA]; // root TAG_Compound
+    data.extend_from_slice(&0_u16.to_be_bytes()); // empty root name
+    for _ in 0 .. depth {
+        data.push(0xA); // nested TAG_Compound field
+        data.extend_from_slice(&0_u16.to_be_bytes()); // empty field name
+    }
+    data
+}
@@ -0,0 +1,140 @@
+use crate::*;
+use byteorder::{BigEndian, WriteBytesExt};
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use std::io::{Result, Write};
+
+/// Serializes the given compound as uncompressed binary NBT, writing it directly to `sink` with
+/// `name` as the root tag's name.
+///
+/// This is the inverse of [`read_nbt_uncompressed`](crate::read_nbt_uncompressed): fields are
+/// written in the order [`NbtCompound::iter`](crate::NbtCompound::iter) yields them. Under the
+/// `preserve_order` feature that is insertion order, so reading a file and writing it back out
+/// reproduces the original field order; without the feature the order is the `HashMap`'s and is
+/// not guaranteed to match the original.
+pub fn write_nbt_uncompressed<W: Write>(sink: &mut W, name: &str, compound: &NbtCompound) -> Result<()> {
+    sink.write_u8(0xA)?;
+    write_string(sink, name)?;
+    write_compound_body(sink, compound)
+}
+
+/// Serializes the given compound as binary NBT, then wraps the output in a zlib encoder before
+/// writing it to `sink`.
+pub fn write_nbt_zlib_compressed<W: Write>(sink: &mut W, name: &str, compound: &NbtCompound) -> Result<()> {
+    let mut encoder = ZlibEncoder::new(sink, Compression::default());
+    write_nbt_uncompressed(&mut encoder, name, compound)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Serializes the given compound as binary NBT, then wraps the output in a gz encoder before
+/// writing it to `sink`.
+pub fn write_nbt_gz_compressed<W: Write>(sink: &mut W, name: &str, compound: &NbtCompound) -> Result<()> {
+    let mut encoder = GzEncoder::new(sink, Compression::default());
+    write_nbt_uncompressed(&mut encoder, name, compound)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+pub(crate) fn write_string<W: Write>(sink: &mut W, s: &str) -> Result<()> {
+    let encoded = cesu8::to_java_cesu8(s);
+    sink.write_u16::<BigEndian>(encoded.len() as u16)?;
+    sink.write_all(&encoded)
+}
+
+fn write_compound_body<W: Write>(sink: &mut W, compound: &NbtCompound) -> Result<()> {
+    for (name, tag) in compound.iter() {
+        sink.write_u8(tag_id(tag))?;
+        write_string(sink, name)?;
+        write_tag_body(sink, tag)?;
+    }
+    sink.write_u8(0x0)
+}
+
+fn write_list_body<W: Write>(sink: &mut W, list: &NbtList) -> Result<()> {
+    let element_id = list.iter().next().map(tag_id).unwrap_or(0x0);
+    sink.write_u8(element_id)?;
+    sink.write_i32::<BigEndian>(list.len() as i32)?;
+    for tag in list.iter() {
+        write_tag_body(sink, tag)?;
+    }
+    Ok(())
+}
+
+fn write_tag_body<W: Write>(sink: &mut W, tag: &NbtTag) -> Result<()> {
+    match tag {
+        NbtTag::Byte(value) => sink.write_i8(*value),
+        NbtTag::Short(value) => sink.write_i16::<BigEndian>(*value),
+        NbtTag::Int(value) => sink.write_i32::<BigEndian>(*value),
+        NbtTag::Long(value) => sink.write_i64::<BigEndian>(*value),
+        NbtTag::Float(value) => sink.write_f32::<BigEndian>(*value),
+        NbtTag::Double(value) => sink.write_f64::<BigEndian>(*value),
+        NbtTag::ByteArray(array) => {
+            sink.write_i32::<BigEndian>(array.len() as i32)?;
+            for &value in array {
+                sink.write_i8(value)?;
+            }
+            Ok(())
+        }
+        NbtTag::String(value) => write_string(sink, value),
+        NbtTag::List(list) => write_list_body(sink, list),
+        NbtTag::Compound(compound) => write_compound_body(sink, compound),
+        NbtTag::IntArray(array) => {
+            sink.write_i32::<BigEndian>(array.len() as i32)?;
+            for &value in array {
+                sink.write_i32::<BigEndian>(value)?;
+            }
+            Ok(())
+        }
+        NbtTag::LongArray(array) => {
+            sink.write_i32::<BigEndian>(array.len() as i32)?;
+            for &value in array {
+                sink.write_i64::<BigEndian>(value)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn tag_id(tag: &NbtTag) -> u8 {
+    match tag {
+        NbtTag::Byte(_) => 0x1,
+        NbtTag::Short(_) => 0x2,
+        NbtTag::Int(_) => 0x3,
+        NbtTag::Long(_) => 0x4,
+        NbtTag::Float(_) => 0x5,
+        NbtTag::Double(_) => 0x6,
+        NbtTag::ByteArray(_) => 0x7,
+        NbtTag::String(_) => 0x8,
+        NbtTag::List(_) => 0x9,
+        NbtTag::Compound(_) => 0xA,
+        NbtTag::IntArray(_) => 0xB,
+        NbtTag::LongArray(_) => 0xC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_nbt_uncompressed;
+
+    #[test]
+    fn round_trips_through_the_tree_reader() {
+        let mut compound = NbtCompound::new();
+        compound.insert("x", NbtTag::Int(42));
+        let mut list = NbtList::new();
+        list.push(NbtTag::Int(1));
+        list.push(NbtTag::Int(2));
+        list.push(NbtTag::Int(3));
+        compound.insert("list", NbtTag::List(list));
+
+        let mut bytes = Vec::new();
+        write_nbt_uncompressed(&mut bytes, "", &compound).unwrap();
+
+        let (read_back, root_name) = read_nbt_uncompressed(&mut &bytes[..]).unwrap();
+        assert_eq!(root_name, "");
+        assert_eq!(read_back, compound);
+    }
+}
@@ -0,0 +1,345 @@
+// A builder-style API for writing binary NBT data directly into a byte buffer without ever
+// constructing an `NbtCompound`/`NbtList` tree, for callers that only need to emit transient
+// output (e.g. assembling a packet).
+
+fn write_str(buf: &mut Vec<u8>, s: &str) {
+    let encoded = cesu8::to_java_cesu8(s);
+    buf.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&encoded);
+}
+
+/// A low-allocation builder which writes big-endian binary NBT straight into an owned `Vec<u8>`,
+/// without ever constructing an `NbtCompound`/`NbtList` tree. Unlike
+/// [`write_nbt_uncompressed`](crate::write_nbt_uncompressed), which serializes an existing tree,
+/// it suits transient output (e.g. assembling a packet) where building and then discarding a full
+/// tag tree would be wasted allocation.
+pub struct NbtWriter {
+    buf: Vec<u8>,
+}
+
+impl NbtWriter {
+    /// Creates a new, empty writer.
+    pub fn new() -> Self {
+        NbtWriter { buf: Vec::new() }
+    }
+
+    /// Writes the root compound header with the given name and returns a [`CompoundWriter`] for
+    /// filling in its fields.
+    pub fn root(&mut self, name: &str) -> CompoundWriter<'_> {
+        self.buf.push(0xA);
+        write_str(&mut self.buf, name);
+        CompoundWriter { buf: &mut self.buf }
+    }
+
+    /// Consumes this writer, returning the bytes written so far.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for NbtWriter {
+    fn default() -> Self {
+        NbtWriter::new()
+    }
+}
+
+/// Writes the fields of a single compound tag.
+///
+/// Obtained from [`NbtWriter::root`] or [`FieldWriter::compound`]. The compound must be closed
+/// with [`finish`](CompoundWriter::finish) once all fields have been written.
+pub struct CompoundWriter<'a> {
+    buf: &'a mut Vec<u8>,
+}
+
+impl<'a> CompoundWriter<'a> {
+    /// Begins writing a named field of this compound.
+    pub fn field<'b>(&'b mut self, name: &'b str) -> FieldWriter<'b> {
+        FieldWriter { buf: self.buf, name }
+    }
+
+    /// Closes this compound by writing the `TAG_End` terminator.
+    pub fn finish(self) {
+        self.buf.push(0x0);
+    }
+}
+
+/// A single named field awaiting a value.
+///
+/// Each method consumes the field writer and appends the corresponding tag's id, name, and
+/// payload to the underlying buffer.
+pub struct FieldWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    name: &'a str,
+}
+
+impl<'a> FieldWriter<'a> {
+    fn header(&mut self, id: u8) {
+        self.buf.push(id);
+        write_str(self.buf, self.name);
+    }
+
+    /// Writes a `TAG_Byte` field.
+    pub fn byte(mut self, value: i8) {
+        self.header(0x1);
+        self.buf.push(value as u8);
+    }
+
+    /// Writes a `TAG_Short` field.
+    pub fn short(mut self, value: i16) {
+        self.header(0x2);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a `TAG_Int` field.
+    pub fn int(mut self, value: i32) {
+        self.header(0x3);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a `TAG_Long` field.
+    pub fn long(mut self, value: i64) {
+        self.header(0x4);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a `TAG_Float` field.
+    pub fn float(mut self, value: f32) {
+        self.header(0x5);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a `TAG_Double` field.
+    pub fn double(mut self, value: f64) {
+        self.header(0x6);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Writes a `TAG_Byte_Array` field.
+    pub fn byte_array(mut self, value: &[i8]) {
+        self.header(0x7);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        self.buf.extend(value.iter().map(|&b| b as u8));
+    }
+
+    /// Writes a `TAG_String` field.
+    pub fn string(mut self, value: &str) {
+        self.header(0x8);
+        write_str(self.buf, value);
+    }
+
+    /// Writes a `TAG_Int_Array` field.
+    pub fn int_array(mut self, value: &[i32]) {
+        self.header(0xB);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        for &element in value {
+            self.buf.extend_from_slice(&element.to_be_bytes());
+        }
+    }
+
+    /// Writes a `TAG_Long_Array` field.
+    pub fn long_array(mut self, value: &[i64]) {
+        self.header(0xC);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        for &element in value {
+            self.buf.extend_from_slice(&element.to_be_bytes());
+        }
+    }
+
+    /// Writes a `TAG_Compound` field header and returns a [`CompoundWriter`] for its fields.
+    pub fn compound(mut self) -> CompoundWriter<'a> {
+        self.header(0xA);
+        CompoundWriter { buf: self.buf }
+    }
+
+    /// Writes a `TAG_List` field header, whose elements are of the given tag id, and returns a
+    /// [`ListWriter`] for its elements.
+    pub fn list(mut self, tag_id: u8) -> ListWriter<'a> {
+        self.header(0x9);
+        self.buf.push(tag_id);
+        let len_pos = self.buf.len();
+        self.buf.extend_from_slice(&0_i32.to_be_bytes());
+        ListWriter {
+            buf: self.buf,
+            tag_id,
+            len_pos,
+            count: 0,
+        }
+    }
+}
+
+/// Writes the elements of a single list tag.
+///
+/// Obtained from [`FieldWriter::list`]. Every element pushed must match the tag id the list was
+/// declared with. The list must be closed with [`finish`](ListWriter::finish), which back-patches
+/// the number of elements pushed into the length slot reserved at the start of the list.
+pub struct ListWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    tag_id: u8,
+    len_pos: usize,
+    count: i32,
+}
+
+impl<'a> ListWriter<'a> {
+    // A real (not debug-only) check: this writer hands callers a raw byte buffer that's shipped
+    // out as-is, so a type-mismatched element must fail loudly rather than silently producing a
+    // corrupt buffer in release builds.
+    fn check_id(&self, id: u8) {
+        assert_eq!(
+            id, self.tag_id,
+            "list element type does not match the type the list was declared with"
+        );
+    }
+
+    /// Pushes a `TAG_Byte` element.
+    pub fn byte(&mut self, value: i8) -> &mut Self {
+        self.check_id(0x1);
+        self.buf.push(value as u8);
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Short` element.
+    pub fn short(&mut self, value: i16) -> &mut Self {
+        self.check_id(0x2);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Int` element.
+    pub fn int(&mut self, value: i32) -> &mut Self {
+        self.check_id(0x3);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Long` element.
+    pub fn long(&mut self, value: i64) -> &mut Self {
+        self.check_id(0x4);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Float` element.
+    pub fn float(&mut self, value: f32) -> &mut Self {
+        self.check_id(0x5);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Double` element.
+    pub fn double(&mut self, value: f64) -> &mut Self {
+        self.check_id(0x6);
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Byte_Array` element.
+    pub fn byte_array(&mut self, value: &[i8]) -> &mut Self {
+        self.check_id(0x7);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        self.buf.extend(value.iter().map(|&b| b as u8));
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_String` element.
+    pub fn string(&mut self, value: &str) -> &mut Self {
+        self.check_id(0x8);
+        write_str(self.buf, value);
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Int_Array` element.
+    pub fn int_array(&mut self, value: &[i32]) -> &mut Self {
+        self.check_id(0xB);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        for &element in value {
+            self.buf.extend_from_slice(&element.to_be_bytes());
+        }
+        self.count += 1;
+        self
+    }
+
+    /// Pushes a `TAG_Long_Array` element.
+    pub fn long_array(&mut self, value: &[i64]) -> &mut Self {
+        self.check_id(0xC);
+        self.buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        for &element in value {
+            self.buf.extend_from_slice(&element.to_be_bytes());
+        }
+        self.count += 1;
+        self
+    }
+
+    /// Begins a `TAG_Compound` element, returning a [`CompoundWriter`] for its fields.
+    pub fn compound(&mut self) -> CompoundWriter<'_> {
+        self.check_id(0xA);
+        self.count += 1;
+        CompoundWriter { buf: self.buf }
+    }
+
+    /// Begins a `TAG_List` element, whose elements are of the given tag id, returning a
+    /// [`ListWriter`] for its elements.
+    pub fn list(&mut self, tag_id: u8) -> ListWriter<'_> {
+        self.check_id(0x9);
+        self.count += 1;
+        self.buf.push(tag_id);
+        let len_pos = self.buf.len();
+        self.buf.extend_from_slice(&0_i32.to_be_bytes());
+        ListWriter {
+            buf: self.buf,
+            tag_id,
+            len_pos,
+            count: 0,
+        }
+    }
+
+    /// Closes this list by back-patching the number of elements pushed into the length slot
+    /// reserved when it was opened.
+    pub fn finish(self) {
+        self.buf[self.len_pos .. self.len_pos + 4].copy_from_slice(&self.count.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_nbt_uncompressed, NbtList, NbtTag};
+
+    #[test]
+    fn writes_bytes_matching_the_tree_reader() {
+        let mut writer = NbtWriter::new();
+        let mut root = writer.root("");
+        root.field("x").int(42);
+        let mut list = root.field("list").list(0x3);
+        list.int(1).int(2).int(3);
+        list.finish();
+        root.finish();
+
+        let bytes = writer.into_bytes();
+        let (compound, root_name) = read_nbt_uncompressed(&mut &bytes[..]).unwrap();
+
+        let mut expected_list = NbtList::new();
+        expected_list.push(NbtTag::Int(1));
+        expected_list.push(NbtTag::Int(2));
+        expected_list.push(NbtTag::Int(3));
+
+        assert_eq!(root_name, "");
+        assert_eq!(compound.get("x"), Some(&NbtTag::Int(42)));
+        assert_eq!(compound.get("list"), Some(&NbtTag::List(expected_list)));
+    }
+
+    #[test]
+    #[should_panic(expected = "list element type does not match")]
+    fn list_rejects_mismatched_element_type() {
+        let mut writer = NbtWriter::new();
+        let mut root = writer.root("");
+        root.field("list").list(0x1).int(1);
+    }
+}